@@ -1,24 +1,40 @@
-use crate::convert::{ConversionError, KdlVersion};
-use crate::convert::{convert_and_write_file_content, convert_file_content};
+use crate::convert::{convert_content, convert_file_content, unpack_content, unpack_file_content};
+use crate::convert::{ConversionError, Direction, KdlVersion, NumberMode};
+use crate::format::InputFormat;
+use miette::Diagnostic;
 use std::env;
 use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
+/// Passing this as `<input>` or `<output>` means "use stdin" / "use stdout"
+/// respectively, following the Unix-filter convention.
+const STDIO_SENTINEL: &str = "-";
+
 const HELP_TEXT: &str = "\
 Usage: jsonkdl [options] [--] <input> <output>
-Converts JSON to KDL.
+Converts JSON to KDL, or KDL back to JSON with --unpack.
 By default, KDL spec v2 is used.
 
 Options:
   -1, --kdl-v1     Convert to KDL v1
   -2, --kdl-v2     Convert to KDL v2
+  -p, --pack       Convert JSON to KDL (default, inferred from <input>)
+  -u, --unpack     Convert KDL to JSON (inferred from <input>)
+  --from <fmt>     Input format: json, toml, or yaml (default, inferred from <input>)
+  -b, --annotate-bignum
+                   Tag numbers that don't fit losslessly in i128/f64 with a
+                   (bigint)/(decimal) KDL type instead of relying on repr
   -f, --force      Overwrite output if it exists
   -v, --verbose    Print extra information during conversion
   -h, --help       Show this help message
 
 Arguments:
-  <input>          Path to input JSON file
-  <output>         Path to output KDL file
+  <input>          Path to input file (JSON/TOML/YAML, or KDL with --unpack),
+                   or - to read from stdin
+  <output>         Path to output file (KDL, or JSON with --unpack),
+                   or - to write to stdout
 ";
 
 #[derive(Debug)]
@@ -26,6 +42,9 @@ pub enum CliError {
     MissingInput,
     HelpRequested,
     MultipleKdlVersion,
+    MultipleDirection,
+    MissingFromValue,
+    UnknownInputFormat(String),
     UnknownOption(String),
     TooManyPositionals,
     NotUnicode(OsString),
@@ -44,14 +63,37 @@ pub struct Args {
     pub force: bool,
     pub verbose: bool,
     pub kdl_version: KdlVersion,
+    pub direction: Direction,
+    pub input_format: InputFormat,
+    pub number_mode: NumberMode,
 }
 
 impl std::fmt::Display for CliError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Conversion errors that carry a located span are rendered as a full
+        // miette report (source snippet + underline) instead of the plain
+        // one-liner every other variant gets. Render the inner
+        // `ConversionError`, not `self` - its `Display` is the plain message
+        // `render_report` prints as the report header, and feeding it `self`
+        // back in would recurse into this same branch forever.
+        if let CliError::Conversion(err) = self {
+            if err.labels().is_some() {
+                return miette::GraphicalReportHandler::new().render_report(f, err);
+            }
+        }
+
         match self {
             CliError::MissingInput => writeln!(f, "missing input path"),
             CliError::HelpRequested => writeln!(f, "help requested"),
             CliError::MultipleKdlVersion => writeln!(f, "specify only one of --kdl-v1 or --kdl-v2"),
+            CliError::MultipleDirection => writeln!(f, "specify only one of --pack or --unpack"),
+            CliError::MissingFromValue => writeln!(f, "--from requires a value"),
+            CliError::UnknownInputFormat(fmt) => {
+                writeln!(
+                    f,
+                    "unknown input format {fmt:?} (expected json, toml, or yaml)"
+                )
+            }
             CliError::UnknownOption(opt) => writeln!(
                 f,
                 "unknown command-line option {opt} (use `--` to pass arbitrary filenames)"
@@ -90,6 +132,22 @@ impl From<ConversionError> for CliError {
     }
 }
 
+impl Diagnostic for CliError {
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        match self {
+            CliError::Conversion(e) => e.source_code(),
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        match self {
+            CliError::Conversion(e) => e.labels(),
+            _ => None,
+        }
+    }
+}
+
 impl Args {
     fn parse() -> Result<Self> {
         let args = env::args_os();
@@ -98,18 +156,21 @@ impl Args {
             return Err(CliError::HelpRequested);
         }
 
-        let args = args.skip(1);
+        let mut args = args.skip(1);
 
         let mut force = false;
         let mut verbose = false;
         let mut kdl_version = None;
+        let mut direction = None;
+        let mut input_format = None;
+        let mut number_mode = NumberMode::default();
 
         let mut positionals_only = false;
 
         let mut input = None;
         let mut output = None;
 
-        for arg in args {
+        while let Some(arg) = args.next() {
             let is_positional;
 
             if positionals_only {
@@ -119,12 +180,13 @@ impl Args {
                     return Err(CliError::NotUnicode(arg));
                 };
 
-                if arg.starts_with("-") {
+                if arg.starts_with("-") && arg != STDIO_SENTINEL {
                     is_positional = false;
                     match arg {
                         "--" => positionals_only = true,
                         "-f" | "--force" => force = true,
                         "-v" | "--verbose" => verbose = true,
+                        "-b" | "--annotate-bignum" => number_mode = NumberMode::Annotated,
                         "-1" | "--kdl-v1" => {
                             if kdl_version.replace(KdlVersion::V1).is_some() {
                                 return Err(CliError::MultipleKdlVersion);
@@ -135,6 +197,27 @@ impl Args {
                                 return Err(CliError::MultipleKdlVersion);
                             }
                         }
+                        "-p" | "--pack" => {
+                            if direction.replace(Direction::Pack).is_some() {
+                                return Err(CliError::MultipleDirection);
+                            }
+                        }
+                        "-u" | "--unpack" => {
+                            if direction.replace(Direction::Unpack).is_some() {
+                                return Err(CliError::MultipleDirection);
+                            }
+                        }
+                        "--from" => {
+                            let value = args.next().ok_or(CliError::MissingFromValue)?;
+                            let Some(value) = value.to_str() else {
+                                return Err(CliError::NotUnicode(value));
+                            };
+
+                            input_format =
+                                Some(InputFormat::from_name(value).ok_or_else(|| {
+                                    CliError::UnknownInputFormat(value.to_string())
+                                })?);
+                        }
                         "-h" | "--help" => return Err(CliError::HelpRequested),
                         _ => return Err(CliError::UnknownOption(arg.to_string())),
                     }
@@ -158,12 +241,18 @@ impl Args {
 
         let input = input.ok_or(CliError::MissingInput)?;
 
+        let direction = direction.unwrap_or_else(|| infer_direction(&input));
+        let input_format = input_format.unwrap_or_else(|| InputFormat::infer(&input));
+
         let result = Self {
             input,
             output,
             force,
             verbose,
             kdl_version,
+            direction,
+            input_format,
+            number_mode,
         };
 
         Ok(result)
@@ -174,6 +263,15 @@ fn print_help() {
     print!("{}", HELP_TEXT);
 }
 
+/// Guesses the conversion direction from the input file's extension,
+/// defaulting to `Pack` (JSON -> KDL) when the extension doesn't say otherwise.
+fn infer_direction(input: &Path) -> Direction {
+    match input.extension().and_then(|ext| ext.to_str()) {
+        Some("kdl") => Direction::Unpack,
+        _ => Direction::Pack,
+    }
+}
+
 pub fn run() -> Result<()> {
     let args = match Args::parse() {
         Ok(args) => args,
@@ -184,26 +282,70 @@ pub fn run() -> Result<()> {
         Err(e) => return Err(e),
     };
 
-    if !args.input.exists() {
-        return Err(CliError::InputNotFound(args.input));
-    }
-
-    if !args.input.is_file() {
-        return Err(CliError::InvalidInputPath(args.input));
-    }
+    let input_is_stdin = args.input == Path::new(STDIO_SENTINEL);
 
-    if let Some(output) = args.output {
-        let output_path = Path::new(&output);
+    if !input_is_stdin {
+        if !args.input.exists() {
+            return Err(CliError::InputNotFound(args.input));
+        }
 
-        if output_path.exists() && !args.force {
-            return Err(CliError::FileExists(output));
+        if !args.input.is_file() {
+            return Err(CliError::InvalidInputPath(args.input));
         }
+    }
 
-        convert_and_write_file_content(&args.input, output_path, args.verbose, args.kdl_version)?;
+    let content = if input_is_stdin {
+        let mut input_content = String::new();
+        io::stdin()
+            .read_to_string(&mut input_content)
+            .map_err(ConversionError::from)?;
+
+        match args.direction {
+            Direction::Pack => convert_content(
+                &input_content,
+                args.input_format,
+                args.kdl_version,
+                args.number_mode,
+            )?,
+            Direction::Unpack => unpack_content(&input_content, args.kdl_version)?,
+        }
     } else {
-        let kdl_content = convert_file_content(&args.input, args.kdl_version)?;
+        match args.direction {
+            Direction::Pack => convert_file_content(
+                &args.input,
+                args.input_format,
+                args.kdl_version,
+                args.number_mode,
+            )?,
+            Direction::Unpack => unpack_file_content(&args.input, args.kdl_version)?,
+        }
+    };
+
+    let output = args
+        .output
+        .filter(|output| output != Path::new(STDIO_SENTINEL));
+
+    match output {
+        Some(output) => {
+            let output_path = Path::new(&output);
+
+            if output_path.exists() && !args.force {
+                return Err(CliError::FileExists(output));
+            }
+
+            fs::write(output_path, &content).map_err(ConversionError::from)?;
 
-        println!("{}", kdl_content);
+            if args.verbose {
+                let input_label = if input_is_stdin {
+                    STDIO_SENTINEL.to_string()
+                } else {
+                    args.input.display().to_string()
+                };
+
+                println!("converted {} -> {}", input_label, output_path.display());
+            }
+        }
+        None => println!("{}", content),
     }
 
     Ok(())
@@ -1,12 +1,266 @@
-use kdl::{KdlDocument, KdlEntry, KdlEntryFormat, KdlIdentifier, KdlNode, KdlValue};
+use crate::format::InputFormat;
+use kdl::{KdlDocument, KdlEntry, KdlEntryFormat, KdlError, KdlIdentifier, KdlNode, KdlValue};
+use miette::{Diagnostic, LabeledSpan, SourceCode};
 use serde_json::Value as JsonValue;
+use std::ops::Range;
 use std::{fmt, fs, path::Path};
 
+/// A step into a JSON value: either an array index or an object key.
+/// Building these up as `convert_node`/`convert_entry`/`convert_type`
+/// recurse lets a structural error report exactly where in the source it
+/// went wrong, instead of just "node must have a name".
+#[derive(Clone, Debug)]
+pub(crate) enum JsonPathSegment {
+    Index(usize),
+    Key(String),
+}
+
+pub(crate) type JsonPath = Vec<JsonPathSegment>;
+
+/// The byte span an `InvalidStructure` error was eventually traced back to,
+/// filled in once the original source text is available (deep in
+/// `convert_node` etc. we only have a parsed `serde_json::Value`, which has
+/// already lost its positions).
+#[derive(Debug)]
+pub(crate) struct StructureLocation {
+    source: String,
+    span: Range<usize>,
+}
+
 #[derive(Debug)]
 pub enum ConversionError {
     Io(std::io::Error),
     JsonParse(serde_json::Error),
-    InvalidStructure(String),
+    KdlParse(KdlError),
+    TomlParse(toml::de::Error),
+    YamlParse(serde_yaml::Error),
+    InvalidStructure {
+        message: String,
+        path: JsonPath,
+        location: Option<StructureLocation>,
+    },
+}
+
+impl ConversionError {
+    pub(crate) fn invalid_structure(message: impl Into<String>, path: &JsonPath) -> Self {
+        ConversionError::InvalidStructure {
+            message: message.into(),
+            path: path.clone(),
+            location: None,
+        }
+    }
+
+    /// Fills in the byte span an `InvalidStructure` error's `path` points to
+    /// by walking the original source text. A no-op for every other variant.
+    fn locate(self, source: &str) -> Self {
+        match self {
+            ConversionError::InvalidStructure {
+                message,
+                path,
+                location: None,
+            } => {
+                let span = locate_json_path(source, &path).unwrap_or(0..source.len());
+
+                ConversionError::InvalidStructure {
+                    message,
+                    path,
+                    location: Some(StructureLocation {
+                        source: source.to_string(),
+                        span,
+                    }),
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+/// Finds the byte span of the JSON value reachable by `path` from the root
+/// of `source`. `serde_json::Value` has already thrown away positions by
+/// the time a structural error is detected, so this re-walks the raw text
+/// instead of the parsed value.
+fn locate_json_path(source: &str, path: &[JsonPathSegment]) -> Option<Range<usize>> {
+    JsonScanner::new(source).locate(path)
+}
+
+struct JsonScanner<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> JsonScanner<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Skips over a complete JSON value starting at the current position.
+    fn skip_value(&mut self) {
+        self.skip_ws();
+
+        match self.peek() {
+            Some('"') => self.skip_string(),
+            Some('{') => self.skip_object(),
+            Some('[') => self.skip_array(),
+            Some(_) => {
+                while self
+                    .peek()
+                    .is_some_and(|c| !matches!(c, ',' | '}' | ']') && !c.is_whitespace())
+                {
+                    self.bump();
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Assumes the current position is a `"`.
+    fn skip_string(&mut self) {
+        self.bump();
+
+        while let Some(c) = self.bump() {
+            match c {
+                '\\' => {
+                    self.bump();
+                }
+                '"' => break,
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_object(&mut self) {
+        self.bump();
+        self.skip_ws();
+
+        if self.peek() == Some('}') {
+            self.bump();
+            return;
+        }
+
+        loop {
+            self.skip_ws();
+            self.skip_string();
+            self.skip_ws();
+            self.bump(); // ':'
+            self.skip_value();
+            self.skip_ws();
+
+            if self.bump() != Some(',') {
+                break;
+            }
+        }
+    }
+
+    fn skip_array(&mut self) {
+        self.bump();
+        self.skip_ws();
+
+        if self.peek() == Some(']') {
+            self.bump();
+            return;
+        }
+
+        loop {
+            self.skip_value();
+            self.skip_ws();
+
+            if self.bump() != Some(',') {
+                break;
+            }
+        }
+    }
+
+    /// Descends into the value starting at the current position following
+    /// `path`, returning the span of the value it bottoms out at.
+    fn locate(&mut self, path: &[JsonPathSegment]) -> Option<Range<usize>> {
+        self.skip_ws();
+        let start = self.pos;
+
+        let Some((segment, rest)) = path.split_first() else {
+            self.skip_value();
+            return Some(start..self.pos);
+        };
+
+        match segment {
+            JsonPathSegment::Index(target) => {
+                if self.peek() != Some('[') {
+                    return None;
+                }
+
+                self.bump();
+                self.skip_ws();
+
+                if self.peek() == Some(']') {
+                    return None;
+                }
+
+                let mut index = 0;
+
+                loop {
+                    if index == *target {
+                        return self.locate(rest);
+                    }
+
+                    self.skip_value();
+                    self.skip_ws();
+
+                    if self.bump() != Some(',') {
+                        return None;
+                    }
+
+                    index += 1;
+                }
+            }
+            JsonPathSegment::Key(target) => {
+                if self.peek() != Some('{') {
+                    return None;
+                }
+
+                self.bump();
+                self.skip_ws();
+
+                if self.peek() == Some('}') {
+                    return None;
+                }
+
+                loop {
+                    self.skip_ws();
+                    let key_start = self.pos;
+                    self.skip_string();
+                    let key = &self.source[key_start + 1..self.pos - 1];
+                    self.skip_ws();
+                    self.bump(); // ':'
+
+                    if key == target {
+                        return self.locate(rest);
+                    }
+
+                    self.skip_value();
+                    self.skip_ws();
+
+                    if self.bump() != Some(',') {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl fmt::Display for ConversionError {
@@ -14,7 +268,12 @@ impl fmt::Display for ConversionError {
         match self {
             ConversionError::Io(err) => write!(f, "io error: {}", err),
             ConversionError::JsonParse(err) => write!(f, "json parsing error: {}", err),
-            ConversionError::InvalidStructure(msg) => write!(f, "invalid json structure: {}", msg),
+            ConversionError::KdlParse(err) => write!(f, "kdl parsing error: {}", err),
+            ConversionError::TomlParse(err) => write!(f, "toml parsing error: {}", err),
+            ConversionError::YamlParse(err) => write!(f, "yaml parsing error: {}", err),
+            ConversionError::InvalidStructure { message, .. } => {
+                write!(f, "invalid json structure: {}", message)
+            }
         }
     }
 }
@@ -24,6 +283,36 @@ impl std::error::Error for ConversionError {
         match self {
             ConversionError::Io(e) => Some(e),
             ConversionError::JsonParse(e) => Some(e),
+            ConversionError::KdlParse(e) => Some(e),
+            ConversionError::TomlParse(e) => Some(e),
+            ConversionError::YamlParse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnostic for ConversionError {
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        match self {
+            ConversionError::InvalidStructure {
+                location: Some(location),
+                ..
+            } => Some(&location.source),
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        match self {
+            ConversionError::InvalidStructure {
+                message,
+                location: Some(location),
+                ..
+            } => Some(Box::new(std::iter::once(LabeledSpan::new(
+                Some(message.clone()),
+                location.span.start,
+                location.span.len(),
+            )))),
             _ => None,
         }
     }
@@ -41,6 +330,12 @@ impl From<serde_json::Error> for ConversionError {
     }
 }
 
+impl From<KdlError> for ConversionError {
+    fn from(err: KdlError) -> Self {
+        ConversionError::KdlParse(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ConversionError>;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
@@ -50,11 +345,46 @@ pub enum KdlVersion {
     V2,
 }
 
-pub fn convert_file_content(input: &Path, version: KdlVersion) -> Result<String> {
-    let json_content = fs::read_to_string(input)?;
-    let json_value: JsonValue = serde_json::from_str(&json_content)?;
+/// Which way data flows through the conversion pipeline: JSON into KDL
+/// (`Pack`, the original and default direction) or KDL back into JSON
+/// (`Unpack`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum Direction {
+    #[default]
+    Pack,
+    Unpack,
+}
 
-    let mut document = convert_document(&json_value)?;
+/// How JSON numbers that don't fit losslessly in `i128`/`f64` are emitted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum NumberMode {
+    /// Stash the literal in the entry's `value_repr` and hope nobody reads
+    /// the (dummy) `KdlValue` directly. The original behavior.
+    #[default]
+    Repr,
+    /// Emit numbers that would otherwise lose precision as a type-annotated
+    /// string instead, e.g. `(bigint)"179769...216"`.
+    Annotated,
+}
+
+pub fn convert_content(
+    content: &str,
+    format: InputFormat,
+    version: KdlVersion,
+    number_mode: NumberMode,
+) -> Result<String> {
+    let json_value = format.parse(content)?;
+
+    // The byte offsets `locate` walks back through only make sense for JSON
+    // source text; for TOML/YAML input the error still carries `path`, it
+    // just can't be pinned to a span in `content`.
+    let mut document = convert_document(&json_value, &Vec::new(), number_mode).map_err(|err| {
+        if format == InputFormat::Json {
+            err.locate(content)
+        } else {
+            err
+        }
+    })?;
 
     // For some reason, you MUST autoformat before ensuring version.
     document.autoformat();
@@ -67,50 +397,76 @@ pub fn convert_file_content(input: &Path, version: KdlVersion) -> Result<String>
     Ok(document.to_string())
 }
 
-pub fn convert_and_write_file_content(
+pub fn convert_file_content(
     input: &Path,
-    output: &Path,
-    verbose: bool,
+    format: InputFormat,
     version: KdlVersion,
-) -> Result<()> {
-    let kdl_doc_content = convert_file_content(input, version)?;
+    number_mode: NumberMode,
+) -> Result<String> {
+    let input_content = fs::read_to_string(input)?;
 
-    fs::write(output, kdl_doc_content)?;
+    convert_content(&input_content, format, version, number_mode)
+}
 
-    if verbose {
-        println!("converted {} -> {}", input.display(), output.display());
-    }
+pub fn unpack_content(content: &str, version: KdlVersion) -> Result<String> {
+    let document = match version {
+        KdlVersion::V1 => KdlDocument::parse_v1(content),
+        KdlVersion::V2 => KdlDocument::parse_v2(content),
+    }?;
+
+    let json_value = convert_document_to_json(&document)?;
 
-    Ok(())
+    Ok(serde_json::to_string_pretty(&json_value)?)
 }
 
-pub fn convert_document(json: &JsonValue) -> Result<KdlDocument> {
+pub fn unpack_file_content(input: &Path, version: KdlVersion) -> Result<String> {
+    let kdl_content = fs::read_to_string(input)?;
+
+    unpack_content(&kdl_content, version)
+}
+
+pub(crate) fn convert_document(
+    json: &JsonValue,
+    path: &JsonPath,
+    number_mode: NumberMode,
+) -> Result<KdlDocument> {
     let json = json.as_array().ok_or_else(|| {
-        ConversionError::InvalidStructure("document root must be an array".to_string())
+        ConversionError::invalid_structure("document root must be an array", path)
     })?;
 
     let mut document = KdlDocument::new();
 
-    for value in json {
-        let node = convert_node(value)?;
+    for (index, value) in json.iter().enumerate() {
+        let mut node_path = path.clone();
+        node_path.push(JsonPathSegment::Index(index));
+
+        let node = convert_node(value, &node_path, number_mode)?;
         document.nodes_mut().push(node);
     }
 
     Ok(document)
 }
 
-fn convert_node(json: &JsonValue) -> Result<KdlNode> {
+fn convert_node(json: &JsonValue, path: &JsonPath, number_mode: NumberMode) -> Result<KdlNode> {
     let json = json
         .as_object()
-        .ok_or_else(|| ConversionError::InvalidStructure("node must be an object".to_string()))?;
+        .ok_or_else(|| ConversionError::invalid_structure("node must be an object", path))?;
+
+    let name_path = {
+        let mut p = path.clone();
+        p.push(JsonPathSegment::Key("name".to_string()));
+        p
+    };
 
     let name = match json.get("name") {
         Some(JsonValue::String(name)) => Ok(name.as_str()),
-        Some(_) => Err(ConversionError::InvalidStructure(
-            "name must be a string".to_string(),
+        Some(_) => Err(ConversionError::invalid_structure(
+            "name must be a string",
+            &name_path,
         )),
-        None => Err(ConversionError::InvalidStructure(
-            "node must have a name".to_string(),
+        None => Err(ConversionError::invalid_structure(
+            "node must have a name",
+            path,
         )),
     }?;
 
@@ -118,24 +474,42 @@ fn convert_node(json: &JsonValue) -> Result<KdlNode> {
 
     // Handle arguments
     if let Some(arguments) = json.get("arguments") {
+        let arguments_path = {
+            let mut p = path.clone();
+            p.push(JsonPathSegment::Key("arguments".to_string()));
+            p
+        };
+
         let arguments = arguments.as_array().ok_or_else(|| {
-            ConversionError::InvalidStructure("arguments must be an array".to_string())
+            ConversionError::invalid_structure("arguments must be an array", &arguments_path)
         })?;
 
-        for arg in arguments {
-            let entry = convert_entry(arg)?;
+        for (index, arg) in arguments.iter().enumerate() {
+            let mut arg_path = arguments_path.clone();
+            arg_path.push(JsonPathSegment::Index(index));
+
+            let entry = convert_entry(arg, &arg_path, number_mode)?;
             node.push(entry);
         }
     }
 
     // Handle properties
     if let Some(properties) = json.get("properties") {
+        let properties_path = {
+            let mut p = path.clone();
+            p.push(JsonPathSegment::Key("properties".to_string()));
+            p
+        };
+
         let properties = properties.as_object().ok_or_else(|| {
-            ConversionError::InvalidStructure("properties must be an object".to_string())
+            ConversionError::invalid_structure("properties must be an object", &properties_path)
         })?;
 
         for (key, prop_value) in properties {
-            let mut entry = convert_entry(prop_value)?;
+            let mut prop_path = properties_path.clone();
+            prop_path.push(JsonPathSegment::Key(key.clone()));
+
+            let mut entry = convert_entry(prop_value, &prop_path, number_mode)?;
             entry.set_name(Some(key.as_str()));
             node.push(entry);
         }
@@ -143,13 +517,25 @@ fn convert_node(json: &JsonValue) -> Result<KdlNode> {
 
     // Handle children
     if let Some(children) = json.get("children") {
-        let children = convert_document(children)?;
+        let children_path = {
+            let mut p = path.clone();
+            p.push(JsonPathSegment::Key("children".to_string()));
+            p
+        };
+
+        let children = convert_document(children, &children_path, number_mode)?;
         node.set_children(children);
     }
 
     // Handle type annotation
     if let Some(ty) = json.get("type") {
-        if let Some(ty) = convert_type(ty)? {
+        let type_path = {
+            let mut p = path.clone();
+            p.push(JsonPathSegment::Key("type".to_string()));
+            p
+        };
+
+        if let Some(ty) = convert_type(ty, &type_path)? {
             node.set_ty(ty);
         }
     }
@@ -157,41 +543,32 @@ fn convert_node(json: &JsonValue) -> Result<KdlNode> {
     Ok(node)
 }
 
-fn convert_entry(json: &JsonValue) -> Result<KdlEntry> {
+fn convert_entry(json: &JsonValue, path: &JsonPath, number_mode: NumberMode) -> Result<KdlEntry> {
     let mut entry = {
         let json = json.get("value").unwrap_or(json);
 
         match json {
             JsonValue::Null => KdlEntry::new(KdlValue::Null),
             JsonValue::Bool(b) => KdlEntry::new(KdlValue::Bool(*b)),
-            JsonValue::Number(n) => {
-                // note: it doesn't matter what value we give to this,
-                // as we never read it and we only print the value_repr,
-                // but it's important that it is a KdlValue::Integer or KdlValue::Float
-                // because those keep their value_repr on `ensure_v1`/`ensure_v2`.
-                // any other KdlValue variant is overwritten.
-                let mut entry = KdlEntry::new(KdlValue::Float(0.0));
-
-                entry.set_format(KdlEntryFormat {
-                    value_repr: n.as_str().into(),
-                    leading: " ".into(),
-                    autoformat_keep: true,
-                    ..Default::default()
-                });
-
-                entry
-            }
+            JsonValue::Number(n) => convert_number(n, number_mode),
             JsonValue::String(s) => KdlEntry::new(KdlValue::String(s.clone())),
             _ => {
-                return Err(ConversionError::InvalidStructure(
-                    "unsupported json value type for kdl conversion".to_string(),
+                return Err(ConversionError::invalid_structure(
+                    "unsupported json value type for kdl conversion",
+                    path,
                 ));
             }
         }
     };
 
     if let Some(ty) = json.get("type") {
-        if let Some(ty) = convert_type(ty)? {
+        let type_path = {
+            let mut p = path.clone();
+            p.push(JsonPathSegment::Key("type".to_string()));
+            p
+        };
+
+        if let Some(ty) = convert_type(ty, &type_path)? {
             entry.set_ty(ty);
         }
     }
@@ -199,12 +576,224 @@ fn convert_entry(json: &JsonValue) -> Result<KdlEntry> {
     Ok(entry)
 }
 
-fn convert_type(json: &JsonValue) -> Result<Option<KdlIdentifier>> {
+fn convert_number(n: &serde_json::Number, number_mode: NumberMode) -> KdlEntry {
+    if number_mode == NumberMode::Annotated {
+        if let Some(ty) = lossy_number_type(n) {
+            let repr = n.as_str();
+
+            let mut entry = KdlEntry::new(KdlValue::String(repr.to_string()));
+            entry.set_ty(KdlIdentifier::from(ty));
+
+            return entry;
+        }
+    }
+
+    // note: it doesn't matter what value we give to this,
+    // as we never read it and we only print the value_repr,
+    // but it's important that it is a KdlValue::Integer or KdlValue::Float
+    // because those keep their value_repr on `ensure_v1`/`ensure_v2`.
+    // any other KdlValue variant is overwritten.
+    let mut entry = KdlEntry::new(KdlValue::Float(0.0));
+
+    entry.set_format(KdlEntryFormat {
+        value_repr: n.as_str().into(),
+        leading: " ".into(),
+        autoformat_keep: true,
+        ..Default::default()
+    });
+
+    entry
+}
+
+/// Type annotations `convert_number` tags a lossy literal with under
+/// `NumberMode::Annotated`.
+const BIGINT_TYPE: &str = "bigint";
+const DECIMAL_TYPE: &str = "decimal";
+
+/// Returns the KDL type annotation to tag `n` with if it can't be stored
+/// losslessly as an `i128` or `f64`, by inspecting `is_i64`/`is_u64` and the
+/// digits of `as_str()` directly rather than round-tripping through either
+/// type (which is exactly the precision loss we're trying to avoid).
+fn lossy_number_type(n: &serde_json::Number) -> Option<&'static str> {
+    if n.is_i64() || n.is_u64() {
+        return None;
+    }
+
+    let repr = n.as_str();
+    let is_integer = !repr.contains(['.', 'e', 'E']);
+
+    if is_integer {
+        return repr.parse::<i128>().is_err().then_some(BIGINT_TYPE);
+    }
+
+    match repr.parse::<f64>() {
+        // An exponent that rounds a non-zero literal to +/-0.0, or that
+        // overflows to +/-inf, loses just as much precision as too many
+        // significant digits does - `significant_digits` alone can't see it,
+        // since it only inspects the mantissa and ignores the exponent.
+        Ok(f) if f.is_finite() => {
+            (f == 0.0 && significant_digits(repr) > 0
+                || significant_digits(repr) > f64::DIGITS as usize)
+                .then_some(DECIMAL_TYPE)
+        }
+        _ => Some(DECIMAL_TYPE),
+    }
+}
+
+/// Counts the decimal digits spanning the first through the last non-zero
+/// digit of `repr`'s mantissa (sign, decimal point, and exponent excluded) -
+/// i.e. how many significant figures the literal actually carries.
+/// Conventional formatting like `1.50` or `100.0` carries far fewer
+/// significant digits than its character count suggests; comparing against
+/// `f64::DIGITS` (the number of decimal digits guaranteed to round-trip
+/// through an `f64` exactly) tells us whether the literal can lose precision,
+/// instead of just whether it happens to differ textually from `f64`'s
+/// minimal `Display` formatting.
+fn significant_digits(repr: &str) -> usize {
+    let mantissa = repr.split(['e', 'E']).next().unwrap_or(repr);
+    let digits: Vec<char> = mantissa.chars().filter(char::is_ascii_digit).collect();
+
+    match (
+        digits.iter().position(|d| *d != '0'),
+        digits.iter().rposition(|d| *d != '0'),
+    ) {
+        (Some(first), Some(last)) => last - first + 1,
+        _ => 0,
+    }
+}
+
+fn convert_type(json: &JsonValue, path: &JsonPath) -> Result<Option<KdlIdentifier>> {
     match json {
         JsonValue::String(ty) => Ok(Some(KdlIdentifier::from(ty.as_str()))),
         JsonValue::Null => Ok(None),
-        _ => Err(ConversionError::InvalidStructure(
-            "type must be a string or null".to_string(),
+        _ => Err(ConversionError::invalid_structure(
+            "type must be a string or null",
+            path,
         )),
     }
 }
+
+pub fn convert_document_to_json(document: &KdlDocument) -> Result<JsonValue> {
+    let nodes = document
+        .nodes()
+        .iter()
+        .map(convert_node_to_json)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(JsonValue::Array(nodes))
+}
+
+fn convert_node_to_json(node: &KdlNode) -> Result<JsonValue> {
+    let mut object = serde_json::Map::new();
+
+    object.insert(
+        "name".to_string(),
+        JsonValue::String(node.name().value().to_string()),
+    );
+
+    let mut arguments = Vec::new();
+    let mut properties = serde_json::Map::new();
+
+    for entry in node.entries() {
+        let value = convert_entry_to_json(entry)?;
+
+        match entry.name() {
+            Some(name) => {
+                properties.insert(name.value().to_string(), value);
+            }
+            None => arguments.push(value),
+        }
+    }
+
+    if !arguments.is_empty() {
+        object.insert("arguments".to_string(), JsonValue::Array(arguments));
+    }
+
+    if !properties.is_empty() {
+        object.insert("properties".to_string(), JsonValue::Object(properties));
+    }
+
+    if let Some(children) = node.children() {
+        object.insert("children".to_string(), convert_document_to_json(children)?);
+    }
+
+    if let Some(ty) = node.ty() {
+        object.insert(
+            "type".to_string(),
+            JsonValue::String(ty.value().to_string()),
+        );
+    }
+
+    Ok(JsonValue::Object(object))
+}
+
+fn convert_entry_to_json(entry: &KdlEntry) -> Result<JsonValue> {
+    if let Some(number) = lossy_annotated_number(entry) {
+        return Ok(number);
+    }
+
+    let value = convert_value_to_json(entry);
+
+    Ok(match entry.ty() {
+        Some(ty) => {
+            let mut object = serde_json::Map::new();
+            object.insert("value".to_string(), value);
+            object.insert(
+                "type".to_string(),
+                JsonValue::String(ty.value().to_string()),
+            );
+            JsonValue::Object(object)
+        }
+        None => value,
+    })
+}
+
+/// Reverses `convert_number`'s `NumberMode::Annotated` encoding: a
+/// `(bigint)`/`(decimal)`-typed string entry is the original JSON number's
+/// digits stashed verbatim to survive precision loss, not a genuine
+/// type-annotated value, so it unpacks straight back to the `serde_json::Number`
+/// those digits represent instead of falling through to the generic
+/// `{"value": ..., "type": ...}` wrapper every other typed entry gets.
+fn lossy_annotated_number(entry: &KdlEntry) -> Option<JsonValue> {
+    let ty = entry.ty()?.value();
+
+    if ty != BIGINT_TYPE && ty != DECIMAL_TYPE {
+        return None;
+    }
+
+    let KdlValue::String(repr) = entry.value() else {
+        return None;
+    };
+
+    serde_json::from_str::<serde_json::Number>(repr)
+        .ok()
+        .map(JsonValue::Number)
+}
+
+// Numbers are reconstructed from the entry's `value_repr` rather than the
+// `KdlValue` itself, since that's where `convert_number` stashes the original
+// literal to survive precision loss (see the comment there). Anything
+// without a format attached (or whose repr doesn't parse back, which
+// shouldn't happen for values this crate produced) falls back to the
+// `KdlValue`.
+fn convert_value_to_json(entry: &KdlEntry) -> JsonValue {
+    if matches!(entry.value(), KdlValue::Integer(_) | KdlValue::Float(_)) {
+        if let Some(repr) = entry.format().map(|format| format.value_repr.as_str()) {
+            if let Ok(number) = serde_json::from_str::<serde_json::Number>(repr) {
+                return JsonValue::Number(number);
+            }
+        }
+    }
+
+    match entry.value() {
+        KdlValue::Null => JsonValue::Null,
+        KdlValue::Bool(b) => JsonValue::Bool(*b),
+        KdlValue::Integer(i) => i64::try_from(*i)
+            .map(JsonValue::from)
+            .unwrap_or_else(|_| JsonValue::from(*i as f64)),
+        KdlValue::Float(f) => {
+            serde_json::Number::from_f64(*f).map_or(JsonValue::Null, JsonValue::Number)
+        }
+        KdlValue::String(s) => JsonValue::String(s.clone()),
+    }
+}
@@ -0,0 +1,78 @@
+use crate::convert::{ConversionError, JsonPath};
+use serde_json::Value as JsonValue;
+use std::path::Path;
+
+/// TOML mandates a table document root, so there's no valid TOML that
+/// deserializes to a top-level JSON array the way `convert_document`
+/// expects. Instead a TOML document nests its node array under this
+/// top-level key, e.g. `[[document]]` tables.
+const TOML_ROOT_KEY: &str = "document";
+
+/// Source format an input document can be written in before being parsed
+/// into the common `serde_json::Value` intermediate that `convert_document`
+/// consumes. Every variant other than `Json` is just a different front door
+/// onto the same pipeline.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum InputFormat {
+    #[default]
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl InputFormat {
+    /// Infers the format from a file's extension, defaulting to `Json` when
+    /// the extension is missing or unrecognized.
+    pub fn infer(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => InputFormat::Toml,
+            Some("yaml") | Some("yml") => InputFormat::Yaml,
+            _ => InputFormat::Json,
+        }
+    }
+
+    /// Looks up a format by the name used for the `--from` CLI flag.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(InputFormat::Json),
+            "toml" => Some(InputFormat::Toml),
+            "yaml" | "yml" => Some(InputFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn parse(self, content: &str) -> Result<JsonValue, ConversionError> {
+        match self {
+            InputFormat::Json => Ok(serde_json::from_str(content)?),
+            InputFormat::Toml => {
+                let root: JsonValue =
+                    toml::from_str(content).map_err(ConversionError::TomlParse)?;
+
+                toml_document_array(root)
+            }
+            InputFormat::Yaml => serde_yaml::from_str(content).map_err(ConversionError::YamlParse),
+        }
+    }
+}
+
+/// Pulls the node array a TOML document nests under `document` back out,
+/// since the TOML parser can only ever hand us a table at the root.
+fn toml_document_array(root: JsonValue) -> Result<JsonValue, ConversionError> {
+    match root {
+        JsonValue::Object(mut map) => match map.remove(TOML_ROOT_KEY) {
+            Some(array @ JsonValue::Array(_)) => Ok(array),
+            _ => Err(toml_root_error()),
+        },
+        _ => Err(toml_root_error()),
+    }
+}
+
+fn toml_root_error() -> ConversionError {
+    ConversionError::invalid_structure(
+        format!(
+            "TOML cannot have an array document root; nest the nodes under a top-level \
+             `{TOML_ROOT_KEY}` array of tables, e.g. `[[{TOML_ROOT_KEY}]]`"
+        ),
+        &JsonPath::new(),
+    )
+}
@@ -6,8 +6,8 @@ use std::{
     path::Path,
 };
 
-use kdl::KdlDocument;
-use miette::{Context, miette};
+use kdl::{KdlDocument, KdlValue};
+use miette::{miette, Context};
 
 mod common;
 
@@ -69,6 +69,68 @@ fn run(name: impl AsRef<Path>, literal: &str) -> miette::Result<()> {
     Ok(())
 }
 
+fn run_annotated(name: impl AsRef<Path>, literal: &str, expected_type: &str) -> miette::Result<()> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("precision");
+
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    let input = dir.join(&name).with_extension("json");
+    let output_v1 = dir.join(&name).with_extension("annotated.v1.kdl");
+    let output_v2 = dir.join(&name).with_extension("annotated.v2.kdl");
+
+    {
+        let mut f = File::create(&input).unwrap();
+        writeln!(f, r#"[ {{ "name": "-", "arguments": [ {literal} ] }} ]"#).unwrap();
+    }
+
+    let (document_v1, document_v2) = {
+        let v1 = {
+            common::run_jsonkdl_v1_annotated(&input, &output_v1)
+                .context(format!("failed when converting {literal}"))?;
+            let output = fs::read_to_string(output_v1).expect("failed to read output kdl");
+            KdlDocument::parse_v1(&output)
+                .map_err(miette::Report::new)
+                .map_err(|err| miette!("output is not valid kdl v1:\n{err:?}"))
+        };
+
+        let v2 = {
+            common::run_jsonkdl_v2_annotated(&input, &output_v2)
+                .context(format!("failed when converting value {literal}"))?;
+            let output = fs::read_to_string(output_v2).expect("failed to read output kdl");
+            KdlDocument::parse_v2(&output)
+                .map_err(miette::Report::new)
+                .map_err(|err| miette!("output is not valid kdl v2:\n{err:?}"))
+        };
+
+        match (v1, v2) {
+            (Ok(v1), Ok(v2)) => (v1, v2),
+            (Err(err), Ok(_)) | (Ok(_), Err(err)) => return Err(err),
+            (Err(v1), Err(v2)) => {
+                return Err(miette!("both outputs are invalid kdl:\n{v1:?}\n{v2:?}\n",));
+            }
+        }
+    };
+
+    for document in [document_v1, document_v2] {
+        let [node] = document.nodes() else { panic!() };
+        let [entry] = node.entries() else { panic!() };
+
+        let ty = entry
+            .ty()
+            .expect("a number tagged as lossy should carry a type annotation")
+            .value();
+        assert_eq!(ty, expected_type, "unexpected type annotation");
+
+        let KdlValue::String(repr) = entry.value() else {
+            panic!("an annotated number should be stored as a string");
+        };
+
+        assert_eq!(literal, repr, "the value has changed during conversion");
+    }
+
+    Ok(())
+}
+
 // 2^2^2^2^2^2^2^2^2^2^2
 const VERY_LARGE_NUMBER: &str = "179769313486231590772930519078902473361797697894230657273430081157732675805500963132708477322407536021120113879871393357658789768814416622492847430639474124377767893424865485276302219601246094119453082952085005768838150682342462881473913110540827237163350510684586298239947245938479716304835356329624224137216";
 
@@ -172,3 +234,106 @@ fn rounds_to_negative_infinity_exp() -> miette::Result<()> {
 fn rounds_to_zero_exp() -> miette::Result<()> {
     floating_point("rounds_to_zero_exp", "1e-10000000", 0.0)
 }
+
+#[test]
+fn annotates_bignum_integer() -> miette::Result<()> {
+    run_annotated("annotates_bignum_integer", VERY_LARGE_NUMBER, "bigint")
+}
+
+#[test]
+fn annotates_lossy_decimal() -> miette::Result<()> {
+    run_annotated(
+        "annotates_lossy_decimal",
+        "1.000000000000000000001",
+        "decimal",
+    )
+}
+
+#[test]
+fn conventional_decimal_is_not_annotated() -> miette::Result<()> {
+    for literal in ["1.0", "2.0", "100.0", "1.50", "3.10", "-5.00"] {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("precision");
+        fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+        let name = format!("conventional_{}", literal.replace(['.', '-'], "_"));
+        let input = dir.join(&name).with_extension("json");
+        let output = dir.join(&name).with_extension("annotated.v2.kdl");
+
+        {
+            let mut f = File::create(&input).unwrap();
+            writeln!(f, r#"[ {{ "name": "-", "arguments": [ {literal} ] }} ]"#).unwrap();
+        }
+
+        common::run_jsonkdl_v2_annotated(&input, &output)
+            .context(format!("failed when converting {literal}"))?;
+
+        let kdl = fs::read_to_string(&output).expect("failed to read output kdl");
+        let document = KdlDocument::parse_v2(&kdl)
+            .map_err(miette::Report::new)
+            .map_err(|err| miette!("output is not valid kdl v2:\n{err:?}"))?;
+
+        let [node] = document.nodes() else { panic!() };
+        let [entry] = node.entries() else { panic!() };
+
+        assert!(
+            entry.ty().is_none(),
+            "{literal} loses no precision in f64 and should not be annotated as lossy"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn annotates_exponent_overflow_and_underflow() -> miette::Result<()> {
+    run_annotated("annotates_exponent_overflow", "1e+400", "decimal")?;
+    run_annotated("annotates_exponent_underflow", "1e-400", "decimal")
+}
+
+/// Packing a literal that would lose precision and then unpacking it must
+/// restore the original JSON number, not the `{"value": ..., "type": ...}`
+/// wrapper every other typed entry gets - that wrapper is for genuine KDL
+/// type annotations, not this crate's own lossy-number encoding.
+fn round_trips_annotated(name: &str, literal: &str) -> miette::Result<()> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("precision");
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    let input = dir.join(name).with_extension("json");
+    let kdl = dir.join(name).with_extension("annotated.kdl");
+    let roundtrip = dir.join(format!("{name}.roundtrip.json"));
+
+    {
+        let mut f = File::create(&input).unwrap();
+        writeln!(f, r#"[ {{ "name": "-", "arguments": [ {literal} ] }} ]"#).unwrap();
+    }
+
+    common::run_jsonkdl_v2_annotated(&input, &kdl)
+        .context(format!("failed when converting {literal}"))?;
+    common::run_jsonkdl_unpack(&kdl, &roundtrip)
+        .context(format!("failed when unpacking {literal}"))?;
+
+    let original: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&input).expect("failed to read input json"))
+            .expect("input fixture is not valid json");
+    let roundtripped: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(&roundtrip).expect("failed to read unpacked json"),
+    )
+    .expect("unpacked output is not valid json");
+
+    assert_eq!(
+        original, roundtripped,
+        "unpacking an annotated bignum/decimal should restore the original number"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn round_trips_annotated_bignum() -> miette::Result<()> {
+    round_trips_annotated("round_trips_annotated_bignum", VERY_LARGE_NUMBER)
+}
+
+#[test]
+fn round_trips_annotated_decimal() -> miette::Result<()> {
+    round_trips_annotated("round_trips_annotated_decimal", "1.000000000000000000001")
+}
@@ -1,11 +1,11 @@
 use std::{
-    fmt::Write,
-    io::Read,
+    fmt::Write as _,
+    io::{Read, Write as _},
     path::Path,
     process::{Command, Stdio},
 };
 
-use miette::{Context, miette};
+use miette::{miette, Context};
 
 fn run(command: &mut Command) -> miette::Result<()> {
     let mut child = command
@@ -62,3 +62,72 @@ pub fn run_jsonkdl_v2(input: &Path, output: &Path) -> miette::Result<()> {
         .arg(output))
     .context(format!("jsonkdl failed on input: {}", input.display()))
 }
+
+pub fn run_jsonkdl_v1_annotated(input: &Path, output: &Path) -> miette::Result<()> {
+    run(Command::new(env!("CARGO_BIN_EXE_jsonkdl"))
+        .arg("-f")
+        .arg("-1")
+        .arg("-b")
+        .arg(input)
+        .arg(output))
+    .context(format!("jsonkdl failed on input: {}", input.display()))
+}
+
+pub fn run_jsonkdl_v2_annotated(input: &Path, output: &Path) -> miette::Result<()> {
+    run(Command::new(env!("CARGO_BIN_EXE_jsonkdl"))
+        .arg("-f")
+        .arg("-2")
+        .arg("-b")
+        .arg(input)
+        .arg(output))
+    .context(format!("jsonkdl failed on input: {}", input.display()))
+}
+
+pub fn run_jsonkdl_unpack(input: &Path, output: &Path) -> miette::Result<()> {
+    run(Command::new(env!("CARGO_BIN_EXE_jsonkdl"))
+        .arg("-f")
+        .arg("-u")
+        .arg(input)
+        .arg(output))
+    .context(format!("jsonkdl --unpack failed on input: {}", input.display()))
+}
+
+/// Runs the binary with arbitrary arguments and returns its combined
+/// stdout+stderr, regardless of exit status. For tests that need to inspect
+/// an error message or diagnostic directly instead of just pass/fail.
+pub fn run_jsonkdl_output(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_jsonkdl"))
+        .args(args)
+        .output()
+        .expect("failed to run binary");
+
+    let mut buf = String::from_utf8_lossy(&output.stdout).into_owned();
+    buf.push_str(&String::from_utf8_lossy(&output.stderr));
+    buf
+}
+
+/// Like [`run_jsonkdl_output`], but feeds `input` to the child's stdin first,
+/// for exercising the `-` stdin sentinel. Returns whether the process
+/// succeeded alongside its combined stdout+stderr.
+pub fn run_jsonkdl_stdin(args: &[&str], input: &str) -> (bool, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jsonkdl"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run binary");
+
+    child
+        .stdin
+        .take()
+        .expect("child stdin was not piped")
+        .write_all(input.as_bytes())
+        .expect("failed to write stdin");
+
+    let output = child.wait_with_output().expect("failed to wait for binary");
+
+    let mut buf = String::from_utf8_lossy(&output.stdout).into_owned();
+    buf.push_str(&String::from_utf8_lossy(&output.stderr));
+    (output.status.success(), buf)
+}
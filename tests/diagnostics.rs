@@ -0,0 +1,54 @@
+use std::fs;
+use std::path::Path;
+
+mod common;
+
+#[test]
+fn invalid_structure_points_at_source_span() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("diagnostics");
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    let input = dir.join("bad.json");
+    let output = dir.join("bad.kdl");
+
+    fs::write(&input, r#"[ { "name": 5 } ]"#).expect("failed to write json fixture");
+
+    let out = common::run_jsonkdl_output(&[
+        "-f",
+        "-2",
+        input.to_str().unwrap(),
+        output.to_str().unwrap(),
+    ]);
+
+    assert!(
+        out.contains("name must be a string"),
+        "expected the diagnostic message, got: {out}"
+    );
+    assert!(
+        out.contains('5'),
+        "expected the rendered snippet to include the offending source text, got: {out}"
+    );
+}
+
+#[test]
+fn yaml_structure_errors_skip_the_json_span() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("diagnostics");
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    let input = dir.join("bad.yaml");
+    let output = dir.join("bad.kdl");
+
+    fs::write(&input, "- name: 5\n").expect("failed to write yaml fixture");
+
+    let out = common::run_jsonkdl_output(&[
+        "-f",
+        "-2",
+        input.to_str().unwrap(),
+        output.to_str().unwrap(),
+    ]);
+
+    assert!(
+        out.contains("name must be a string"),
+        "expected the plain diagnostic message even without a located span, got: {out}"
+    );
+}
@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::Path;
+
+use kdl::KdlDocument;
+use miette::Context;
+
+mod common;
+
+#[test]
+fn toml_input_converts() -> miette::Result<()> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("formats");
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    let input = dir.join("input.toml");
+    let output = dir.join("input.kdl");
+
+    fs::write(
+        &input,
+        r#"
+        [[document]]
+        name = "greeting"
+
+        [document.properties]
+        text = "hello"
+        "#,
+    )
+    .expect("failed to write toml fixture");
+
+    common::run_jsonkdl_v2(&input, &output)?;
+
+    let kdl = fs::read_to_string(&output).expect("failed to read output kdl");
+    let document = KdlDocument::parse_v2(&kdl).context("output is not valid kdl")?;
+
+    let [node] = document.nodes() else {
+        panic!("expected a single node, got: {kdl}");
+    };
+    assert_eq!(node.name().value(), "greeting");
+
+    Ok(())
+}
+
+#[test]
+fn toml_without_document_array_is_rejected() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("formats");
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    let input = dir.join("no_root.toml");
+    let output = dir.join("no_root.kdl");
+
+    fs::write(&input, "name = \"greeting\"\n").expect("failed to write toml fixture");
+
+    let out = common::run_jsonkdl_output(&[
+        "-f",
+        "-2",
+        input.to_str().unwrap(),
+        output.to_str().unwrap(),
+    ]);
+
+    assert!(
+        out.contains("document"),
+        "expected the error to mention the required `document` array, got: {out}"
+    );
+}
+
+#[test]
+fn yaml_input_converts() -> miette::Result<()> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("formats");
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    let input = dir.join("input.yaml");
+    let output = dir.join("input.kdl");
+
+    fs::write(&input, "- name: greeting\n  properties:\n    text: hello\n")
+        .expect("failed to write yaml fixture");
+
+    common::run_jsonkdl_v2(&input, &output)?;
+
+    let kdl = fs::read_to_string(&output).expect("failed to read output kdl");
+    let document = KdlDocument::parse_v2(&kdl).context("output is not valid kdl")?;
+
+    let [node] = document.nodes() else {
+        panic!("expected a single node, got: {kdl}");
+    };
+    assert_eq!(node.name().value(), "greeting");
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+mod common;
+
+#[test]
+fn stdin_and_stdout_sentinel_round_trip() {
+    let input = r#"[ { "name": "greeting" } ]"#;
+    let (success, output) = common::run_jsonkdl_stdin(&["-2", "-", "-"], input);
+
+    assert!(success, "jsonkdl failed on stdin input: {output}");
+    assert!(
+        output.contains("greeting"),
+        "expected converted output to contain the node name, got: {output}"
+    );
+}
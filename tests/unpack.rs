@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::Path;
+
+mod common;
+
+/// JSON fixtures covering the node shapes `convert_document_to_json` has to
+/// reconstruct: bare arguments, properties, nested children, and a type
+/// annotation.
+const FIXTURES: &[(&str, &str)] = &[
+    ("flat", r#"[ { "name": "greeting", "arguments": [ "hello" ] } ]"#),
+    (
+        "properties",
+        r#"[ { "name": "point", "properties": { "x": 1, "y": 2 } } ]"#,
+    ),
+    (
+        "nested",
+        r#"[ { "name": "parent", "children": [ { "name": "child", "arguments": [ true, null ] } ] } ]"#,
+    ),
+    (
+        "typed",
+        r#"[ { "name": "value", "arguments": [ { "value": 5, "type": "u8" } ] } ]"#,
+    ),
+];
+
+#[test]
+fn unpack_round_trips_fixtures() -> miette::Result<()> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("unpack");
+    fs::create_dir_all(&dir).expect("failed to create test output directory");
+
+    for (name, json) in FIXTURES {
+        let input = dir.join(name).with_extension("json");
+        let kdl_path = dir.join(name).with_extension("kdl");
+        let roundtrip_path = dir.join(format!("{name}.roundtrip.json"));
+
+        fs::write(&input, json).expect("failed to write json fixture");
+
+        common::run_jsonkdl_v2(&input, &kdl_path)?;
+        common::run_jsonkdl_unpack(&kdl_path, &roundtrip_path)?;
+
+        let original: serde_json::Value =
+            serde_json::from_str(json).expect("fixture is not valid json");
+        let roundtripped: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(&roundtrip_path).expect("failed to read unpacked json"),
+        )
+        .expect("unpacked output is not valid json");
+
+        assert_eq!(original, roundtripped, "unpack did not round-trip {name}");
+    }
+
+    Ok(())
+}